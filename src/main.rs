@@ -2,15 +2,22 @@ use std::path::Path;
 
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
-    input::mouse::MouseWheel,
+    core::FixedTimestep,
+    input::{
+        keyboard::KeyCode,
+        mouse::{MouseButton, MouseMotion, MouseWheel},
+    },
     math::Quat,
     prelude::*,
-    render::camera::{Camera, OrthographicProjection},
+    render::camera::{ActiveCameras, OrthographicProjection},
     window::{WindowId, WindowResized},
 };
 
 use rand::Rng;
 
+mod post_process;
+use post_process::{setup_post_process, toggle_pixelate, OffscreenCamera, PixelateSettings};
+
 struct PrintTimer(Timer);
 struct Position(Transform);
 enum Direction {
@@ -19,10 +26,70 @@ enum Direction {
 }
 struct RotationRate(f32);
 
+/// Flattens `SpriteBundle` alongside `Direction`/`RotationRate` into a single
+/// `Bundle` so a `Vec<TileBundle>` can go straight into `spawn_batch` — a
+/// tuple of `(SpriteBundle, Direction, RotationRate)` doesn't implement
+/// `Bundle` because `SpriteBundle` is itself a bundle, not a `Component`.
+#[derive(Bundle)]
+struct TileBundle {
+    #[bundle]
+    sprite: SpriteBundle,
+    direction: Direction,
+    rotation_rate: RotationRate,
+}
+
+/// Whether sprites should be spread across several tinted materials instead
+/// of sharing one, forcing the renderer into multiple batches.
+struct ColorTint(bool);
+
+/// Marks the single camera that `move_camera`/`zoom_camera` are allowed to
+/// drive; the fixed tour cameras are left alone by those systems.
+struct UserCamera;
+
+/// The full set of cameras spawned in `setup`, in cycle order, with the
+/// index of whichever one is currently rendering. `cameras[0]` is always the
+/// `UserCamera`.
+struct CameraList {
+    cameras: Vec<Entity>,
+    active: usize,
+}
+
+/// The point the user camera is centered on in `CameraMode::Manual`; the
+/// `Orbit` path in `move_camera` does not use it.
+struct Target;
+
+/// Whether the user camera follows its fixed orbit path or is free to be
+/// panned/zoomed by the player.
+#[derive(PartialEq)]
+enum CameraMode {
+    Orbit,
+    Manual,
+}
+
+/// Zoom distance of the user camera in `CameraMode::Manual`, which
+/// `camera_follow` maps onto the orthographic projection scale (the camera's
+/// translation is not moved back); driven by the scroll wheel.
+struct FollowDistance(f32);
+
+/// Name `OrthographicCameraBundle::new_2d()` gives every camera it builds;
+/// `ActiveCameras` tracks, per name, which single entity is currently
+/// rendered under it — that's the knob `cycle_cameras` has to turn, since
+/// flipping a per-entity "active" flag isn't how this Bevy version picks
+/// which camera renders.
+const CAMERA_2D: &str = "Camera2d";
+
 const CAMERA_SPEED: f32 = 10.0;
 const SCALE_FACTOR: f32 = 0.025;
+const DISTANCE_FACTOR: f32 = 20.0;
+const PAN_SPEED: f32 = 2.0;
+/// Simulation rate for `rotate_entity`/`move_camera`, in seconds per step.
+/// Kept separate from render frame rate so the scene reaches the same state
+/// after N steps regardless of how fast the machine renders.
+const FIXED_TIMESTEP: f64 = 1.0 / 60.0;
 
 fn main() {
+    let color_tint = ColorTint(std::env::args().any(|arg| arg == "--colored"));
+
     App::build()
         .add_plugin(LogDiagnosticsPlugin::default())
         .add_plugin(FrameTimeDiagnosticsPlugin::default())
@@ -30,12 +97,29 @@ fn main() {
             level: bevy::log::Level::DEBUG,
             ..Default::default()
         })
+        .insert_resource(color_tint)
+        .insert_resource(CameraMode::Orbit)
+        .insert_resource(FollowDistance(1000.0))
+        .insert_resource(PixelateSettings::default())
         .add_plugins(DefaultPlugins)
         .add_startup_system(setup.system())
+        .add_startup_system(setup_post_process.system())
         .add_system(tick.system().label("Tick"))
-        .add_system(rotate_entity.system().after("Tick").label("Game"))
-        .add_system(move_camera.system().after("Game"))
+        .add_system(toggle_pixelate.system())
+        .add_system(toggle_camera_mode.system().before("Game"))
+        .add_system_set(
+            SystemSet::new()
+                .label("Game")
+                .after("Tick")
+                .with_run_criteria(FixedTimestep::step(FIXED_TIMESTEP))
+                .with_system(rotate_entity.system())
+                .with_system(move_camera.system()),
+        )
         .add_system(zoom_camera.system().after("Game"))
+        .add_system(pan_target.system().after("Game"))
+        .add_system(camera_follow.system().after("Game"))
+        .add_system(cycle_cameras.system().label("Cycle"))
+        .add_system(sync_offscreen_camera.system().after("Cycle"))
         .run()
 }
 
@@ -43,6 +127,7 @@ fn setup(
     mut commands: Commands,
     assets: Res<AssetServer>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    color_tint: Res<ColorTint>,
 ) {
     let mut rng = rand::thread_rng();
 
@@ -53,16 +138,66 @@ fn setup(
     let half_y = (map_size.y / 32.0) as i32;
 
     let sprite_path = Path::new("branding").join("icon.png");
-    let sprite_handle = materials.add(assets.load(sprite_path).into());
+    let sprite_handle = materials.add(assets.load(sprite_path.clone()).into());
 
-    commands
+    // When colored mode is on, cycle through a small palette of tinted
+    // materials so adjacent sprites rarely share one, forcing many batches.
+    let palette: Vec<Handle<ColorMaterial>> = if color_tint.0 {
+        [Color::BLUE, Color::WHITE, Color::RED]
+            .iter()
+            .map(|&color| {
+                materials.add(ColorMaterial {
+                    color,
+                    texture: Some(assets.load(sprite_path.clone())),
+                })
+            })
+            .collect()
+    } else {
+        vec![sprite_handle]
+    };
+
+    let user_camera = commands
         .spawn()
         .insert_bundle(OrthographicCameraBundle::new_2d())
+        .insert(UserCamera)
         .insert(PrintTimer(Timer::from_seconds(1.0, true)))
         .insert(Position(Transform::from_translation(Vec3::new(
             0.0, 0.0, 1000.0,
-        ))));
+        ))))
+        .id();
+
+    // A handful of fixed vantage points for tour mode, each parked at a
+    // different position/zoom so cycling through them inspects frustum
+    // culling from several angles.
+    let tour_spots: &[(Vec3, f32)] = &[
+        (Vec3::new(2000.0, 0.0, 1000.0), 1.0),
+        (Vec3::new(-2000.0, 2000.0, 1000.0), 0.4),
+        (Vec3::new(0.0, -2000.0, 1000.0), 0.2),
+    ];
 
+    let mut cameras = vec![user_camera];
+    for &(translation, scale) in tour_spots {
+        let mut camera_bundle = OrthographicCameraBundle::new_2d();
+        camera_bundle.transform = Transform::from_translation(translation);
+        camera_bundle.orthographic_projection.scale = scale;
+
+        let tour_camera = commands.spawn().insert_bundle(camera_bundle).id();
+        cameras.push(tour_camera);
+    }
+
+    commands.insert_resource(CameraList { cameras, active: 0 });
+
+    commands
+        .spawn()
+        .insert(Target)
+        .insert(Transform::identity())
+        .insert(GlobalTransform::identity());
+
+    // Build the full set of tile bundles up front so `spawn_batch` can insert
+    // them all into a single archetype in one pass, instead of paying for an
+    // individual command-buffer entry and archetype move per tile.
+    let mut palette_index = 0;
+    let mut tiles = Vec::with_capacity((half_x * 2 * half_y * 2) as usize);
     for y in -half_y..half_y {
         for x in -half_x..half_x {
             let position = Vec2::new(x as f32, y as f32);
@@ -70,10 +205,18 @@ fn setup(
             let rotation = Quat::from_rotation_z(rng.gen::<f32>());
             let scale = Vec3::splat(rng.gen::<f32>() * 2.0);
 
-            commands
-                .spawn()
-                .insert_bundle(SpriteBundle {
-                    material: sprite_handle.clone(),
+            let material = palette[palette_index % palette.len()].clone();
+            palette_index += 1;
+
+            let direction = if rng.gen::<f32>() > 0.5 {
+                Direction::CounterClockwise
+            } else {
+                Direction::Clockwise
+            };
+
+            tiles.push(TileBundle {
+                sprite: SpriteBundle {
+                    material,
                     transform: Transform {
                         translation,
                         rotation,
@@ -81,47 +224,62 @@ fn setup(
                     },
                     sprite: Sprite::new(tile_size),
                     ..Default::default()
-                })
-                .insert(if rng.gen::<f32>() > 0.5 {
-                    Direction::CounterClockwise
-                } else {
-                    Direction::Clockwise
-                })
-                .insert(RotationRate(rng.gen::<f32>() * 5.0));
+                },
+                direction,
+                rotation_rate: RotationRate(rng.gen::<f32>() * 5.0),
+            });
         }
     }
+    commands.spawn_batch(tiles);
 }
 
-fn rotate_entity(time: Res<Time>, mut query: Query<(&mut Transform, &Direction, &RotationRate)>) {
+fn rotate_entity(mut query: Query<(&mut Transform, &Direction, &RotationRate)>) {
+    let dt = FIXED_TIMESTEP as f32;
     for (mut transform, direction, rate) in query.iter_mut() {
         let rotation_direction = match *direction {
             Direction::Clockwise => 1.0 as f32,
             Direction::CounterClockwise => -1.0 as f32,
         };
-        transform.rotation *=
-            Quat::from_rotation_z(time.delta_seconds() * rotation_direction * rate.0);
+        transform.rotation *= Quat::from_rotation_z(dt * rotation_direction * rate.0);
     }
 }
 
-fn move_camera(time: Res<Time>, mut query: Query<(&mut Transform, &mut Position), With<Camera>>) {
+fn move_camera(
+    mode: Res<CameraMode>,
+    mut query: Query<(&mut Transform, &mut Position), With<UserCamera>>,
+) {
+    if *mode != CameraMode::Orbit {
+        return;
+    }
+
+    let dt = FIXED_TIMESTEP as f32;
     for (mut transform, mut position) in query.iter_mut() {
-        position
-            .0
-            .rotate(Quat::from_rotation_z(time.delta_seconds() * 0.5));
-        position.0 =
-            position.0 * Transform::from_translation(Vec3::X * CAMERA_SPEED * time.delta_seconds());
+        position.0.rotate(Quat::from_rotation_z(dt * 0.5));
+        position.0 = position.0 * Transform::from_translation(Vec3::X * CAMERA_SPEED * dt);
         transform.translation = position.0.translation;
-        transform.rotation *= Quat::from_rotation_z(time.delta_seconds() / 2.0);
+        transform.rotation *= Quat::from_rotation_z(dt / 2.0);
     }
 }
 
+/// In `CameraMode::Manual`, `camera_follow` is the sole writer of
+/// `projection.scale` (driven off `follow_distance`, updated below); writing
+/// it here too would race with that system since neither is ordered against
+/// the other, so this only touches `projection.scale` directly in `Orbit`.
 fn zoom_camera(
+    mode: Res<CameraMode>,
     windows: Res<Windows>,
-    mut projection_query: Query<&mut OrthographicProjection>,
+    mut follow_distance: ResMut<FollowDistance>,
+    mut projection_query: Query<&mut OrthographicProjection, With<UserCamera>>,
     mut mouse_wheel_events: EventReader<MouseWheel>,
     mut window_resized: EventWriter<WindowResized>,
 ) {
     for event in mouse_wheel_events.iter() {
+        follow_distance.0 = (follow_distance.0 - event.y * DISTANCE_FACTOR).max(10.0);
+
+        if *mode != CameraMode::Orbit {
+            continue;
+        }
+
         for mut projection in projection_query.iter_mut() {
             projection.scale = (projection.scale - event.y * SCALE_FACTOR)
                 .max(0.1)
@@ -137,6 +295,111 @@ fn zoom_camera(
     }
 }
 
+/// `F` swaps the user camera between its automatic orbit path and manual
+/// free-look (drag-pan + follow target).
+fn toggle_camera_mode(keyboard: Res<Input<KeyCode>>, mut mode: ResMut<CameraMode>) {
+    if !keyboard.just_pressed(KeyCode::F) {
+        return;
+    }
+
+    *mode = match *mode {
+        CameraMode::Orbit => CameraMode::Manual,
+        CameraMode::Manual => CameraMode::Orbit,
+    };
+}
+
+/// Left-drag pans `Target` while in `CameraMode::Manual`.
+fn pan_target(
+    mode: Res<CameraMode>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut query: Query<&mut Transform, With<Target>>,
+) {
+    if *mode != CameraMode::Manual || !mouse_buttons.pressed(MouseButton::Left) {
+        return;
+    }
+
+    for event in mouse_motion_events.iter() {
+        for mut transform in query.iter_mut() {
+            transform.translation.x -= event.delta.x * PAN_SPEED;
+            transform.translation.y += event.delta.y * PAN_SPEED;
+        }
+    }
+}
+
+/// Reference `FollowDistance` that maps to an unzoomed (`scale == 1.0`)
+/// orthographic projection in `camera_follow`.
+const REFERENCE_DISTANCE: f32 = 1000.0;
+
+/// In `CameraMode::Manual`, centers the user camera on `Target` in the XY
+/// plane and maps `FollowDistance` onto the orthographic projection scale.
+/// An orthographic camera doesn't zoom by moving along Z the way a
+/// perspective one does, so "distance" has to drive `scale` directly rather
+/// than translation, or scrolling would be visibly inert.
+fn camera_follow(
+    mode: Res<CameraMode>,
+    follow_distance: Res<FollowDistance>,
+    target_query: Query<&Transform, (With<Target>, Without<UserCamera>)>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<UserCamera>>,
+) {
+    if *mode != CameraMode::Manual {
+        return;
+    }
+
+    let target_translation = match target_query.iter().next() {
+        Some(target) => target.translation,
+        None => return,
+    };
+
+    for (mut transform, mut projection) in camera_query.iter_mut() {
+        transform.translation.x = target_translation.x;
+        transform.translation.y = target_translation.y;
+        projection.scale = (follow_distance.0 / REFERENCE_DISTANCE).max(0.1).min(1.0);
+    }
+}
+
+/// Pressing `C` advances to the next camera in `CameraList`, wrapping back to
+/// the user camera, by re-registering which entity `ActiveCameras` treats as
+/// the one rendered under the shared `"Camera2d"` name.
+fn cycle_cameras(
+    keyboard: Res<Input<KeyCode>>,
+    mut camera_list: ResMut<CameraList>,
+    mut active_cameras: ResMut<ActiveCameras>,
+) {
+    if !keyboard.just_pressed(KeyCode::C) {
+        return;
+    }
+
+    camera_list.active = (camera_list.active + 1) % camera_list.cameras.len();
+    let entity = camera_list.cameras[camera_list.active];
+
+    if let Some(active_camera) = active_cameras.get_mut(CAMERA_2D) {
+        active_camera.entity = Some(entity);
+    }
+}
+
+/// Keeps the post-process pass's offscreen camera framing whichever
+/// `CameraList` entry is currently active, so cycling to a tour camera
+/// doesn't leave the pixelation/quantization pass sampling a stale view.
+fn sync_offscreen_camera(
+    camera_list: Res<CameraList>,
+    source_query: Query<(&Transform, &OrthographicProjection), Without<OffscreenCamera>>,
+    mut offscreen_query: Query<(&mut Transform, &mut OrthographicProjection), With<OffscreenCamera>>,
+) {
+    let active_entity = camera_list.cameras[camera_list.active];
+    let (source_transform, source_projection) = match source_query.get(active_entity) {
+        Ok(source) => source,
+        Err(_) => return,
+    };
+    let source_transform = *source_transform;
+    let source_scale = source_projection.scale;
+
+    for (mut transform, mut projection) in offscreen_query.iter_mut() {
+        *transform = source_transform;
+        projection.scale = source_scale;
+    }
+}
+
 fn tick(time: Res<Time>, sprites: Query<&Sprite>, mut query: Query<&mut PrintTimer>) {
     for mut timer in query.iter_mut() {
         timer.0.tick(time.delta());
@@ -0,0 +1,292 @@
+//! Full-screen pixelation / color-quantization post-process pass.
+//!
+//! This mirrors the shape of Bevy 0.5's built-in render-to-texture example:
+//! a dedicated camera + [`PassNode`] renders the same `MainPass`-tagged
+//! scene a second time into an offscreen texture, and a third camera +
+//! pass draws a single full-screen quad over the window that samples that
+//! texture through [`PIXELATE_FRAGMENT_SHADER`] — which snaps UVs to a
+//! coarse grid and rounds color to a fixed number of levels. The quad pass
+//! runs after the normal `MAIN_PASS` and fully covers the window, so the
+//! plain scene MAIN_PASS already drew there is entirely overdrawn by the
+//! processed result. `PixelateMaterial`'s uniform/texture bindings are fed
+//! by an [`AssetRenderResourcesNode`] wired into `QUAD_PASS`, same as any
+//! other custom material; the offscreen texture it samples is tied to the
+//! graph by handing `OFFSCREEN_TEXTURE`'s backing [`Handle<Texture>`]
+//! straight to the [`TextureNode`] constructor rather than via a node edge.
+
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        camera::{ActiveCameras, Camera},
+        pass::{
+            LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor,
+            TextureAttachment,
+        },
+        pipeline::{PipelineDescriptor, RenderPipeline},
+        render_graph::{
+            base::{self, MainPass},
+            AssetRenderResourcesNode, CameraNode, PassNode, RenderGraph, TextureNode,
+            WindowSwapChainNode,
+        },
+        renderer::RenderResources,
+        shader::{ShaderStage, ShaderStages},
+        texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
+    },
+};
+
+const OFFSCREEN_CAMERA: &str = "PixelateOffscreenCamera";
+const QUAD_CAMERA: &str = "PixelateQuadCamera";
+const OFFSCREEN_PASS: &str = "pixelate_offscreen_pass";
+const QUAD_PASS: &str = "pixelate_quad_pass";
+const OFFSCREEN_TEXTURE: &str = "pixelate_offscreen_texture";
+const PIXELATE_MATERIAL_NODE: &str = "pixelate_material_node";
+
+/// Tags the camera the offscreen pass renders through, so `main.rs` can keep
+/// it framing the same view as whichever `CameraList` entry is currently
+/// active (otherwise cycling to a tour camera would leave the post-process
+/// pass sampling a stale view — see `sync_offscreen_camera` in `main.rs`).
+pub struct OffscreenCamera;
+
+/// Tags the full-screen quad so `QUAD_PASS` draws only it, not the tiles the
+/// offscreen pass already captured.
+struct QuadPass;
+
+/// Pixel resolution (`N`) and color level count (`L`) for the post-process
+/// shader, plus whether the pass is currently applied. Adjustable at
+/// runtime; `toggle_pixelate` flips `enabled`.
+pub struct PixelateSettings {
+    pub pixels: f32,
+    pub levels: f32,
+    pub enabled: bool,
+}
+
+impl Default for PixelateSettings {
+    fn default() -> Self {
+        PixelateSettings {
+            pixels: 160.0,
+            levels: 8.0,
+            enabled: false,
+        }
+    }
+}
+
+/// Shader-bound uniforms mirrored from `PixelateSettings`, plus the handle
+/// to the offscreen texture `OFFSCREEN_PASS` rendered the scene into.
+#[derive(RenderResources, TypeUuid)]
+#[uuid = "a84c35c8-7f02-4b1e-9b0f-5e3f2a9c6d41"]
+struct PixelateMaterial {
+    pub pixels: f32,
+    pub levels: f32,
+    pub enabled: f32,
+    pub texture: Handle<Texture>,
+}
+
+const PIXELATE_VERTEX_SHADER: &str = r#"
+#version 450
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 1) in vec2 Vertex_Uv;
+layout(location = 0) out vec2 v_Uv;
+layout(set = 0, binding = 0) uniform CameraViewProj { mat4 ViewProj; };
+layout(set = 1, binding = 0) uniform Transform { mat4 Model; };
+void main() {
+    v_Uv = Vertex_Uv;
+    gl_Position = ViewProj * Model * vec4(Vertex_Position, 1.0);
+}
+"#;
+
+const PIXELATE_FRAGMENT_SHADER: &str = r#"
+#version 450
+layout(location = 0) in vec2 v_Uv;
+layout(location = 0) out vec4 o_Target;
+layout(set = 2, binding = 0) uniform PixelateMaterial_pixels { float pixels; };
+layout(set = 2, binding = 1) uniform PixelateMaterial_levels { float levels; };
+layout(set = 2, binding = 2) uniform PixelateMaterial_enabled { float enabled; };
+layout(set = 2, binding = 3) uniform texture2D PixelateMaterial_texture;
+layout(set = 2, binding = 4) uniform sampler PixelateMaterial_texture_sampler;
+void main() {
+    vec2 uv = v_Uv;
+    if (enabled > 0.5) {
+        uv = floor(uv * pixels) / pixels;
+    }
+    vec4 col = texture(sampler2D(PixelateMaterial_texture, PixelateMaterial_texture_sampler), uv);
+    if (enabled > 0.5) {
+        col = round(col * levels) / levels;
+    }
+    o_Target = col;
+}
+"#;
+
+/// Wires up the offscreen-capture pass, the quad-presentation pass, and
+/// their two dedicated cameras. Doesn't touch the existing `CameraList`
+/// cameras at all, so it has no dependency on their spawn order.
+pub fn setup_post_process(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    mut active_cameras: ResMut<ActiveCameras>,
+    mut render_graph: ResMut<RenderGraph>,
+    mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+    mut shaders: ResMut<Assets<Shader>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<PixelateMaterial>>,
+) {
+    let window = windows.get_primary().expect("primary window");
+    let size = Extent3d::new(window.width() as u32, window.height() as u32, 1);
+
+    let render_target = textures.add(Texture::default());
+
+    // Passing `render_target` to the `TextureNode` itself (rather than
+    // wiring it in afterwards via a node edge keyed by the handle) is what
+    // ties the graph-local texture the offscreen pass renders into to the
+    // same `Handle<Texture>` asset `PixelateMaterial` samples from.
+    render_graph.add_node(
+        OFFSCREEN_TEXTURE,
+        TextureNode::new(
+            TextureDescriptor {
+                size,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Bgra8UnormSrgb,
+                usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED,
+                ..Default::default()
+            },
+            None,
+            Some(render_target.clone_weak()),
+        ),
+    );
+
+    render_graph.add_system_node(OFFSCREEN_CAMERA, CameraNode::new(OFFSCREEN_CAMERA));
+    render_graph.add_node(
+        OFFSCREEN_PASS,
+        PassNode::<&MainPass>::new(PassDescriptor {
+            color_attachments: vec![RenderPassColorAttachmentDescriptor {
+                attachment: TextureAttachment::Input("color_attachment".to_string()),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::NONE),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+            sample_count: 1,
+        }),
+    );
+    render_graph
+        .add_node_edge(OFFSCREEN_CAMERA, OFFSCREEN_PASS)
+        .expect("wire offscreen camera into offscreen pass");
+    render_graph
+        .add_slot_edge(OFFSCREEN_TEXTURE, TextureNode::TEXTURE, OFFSCREEN_PASS, "color_attachment")
+        .expect("wire offscreen texture into offscreen pass");
+    active_cameras.add(OFFSCREEN_CAMERA);
+
+    render_graph.add_system_node(QUAD_CAMERA, CameraNode::new(QUAD_CAMERA));
+    render_graph.add_node(
+        QUAD_PASS,
+        PassNode::<&QuadPass>::new(PassDescriptor {
+            color_attachments: vec![RenderPassColorAttachmentDescriptor {
+                attachment: TextureAttachment::Input("color_attachment".to_string()),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+            sample_count: 1,
+        }),
+    );
+    render_graph
+        .add_node_edge(QUAD_CAMERA, QUAD_PASS)
+        .expect("wire quad camera into quad pass");
+    render_graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            QUAD_PASS,
+            "color_attachment",
+        )
+        .expect("wire window swap chain into quad pass");
+    // Both orderings matter: the quad must draw after the plain scene so it
+    // fully overdraws it, and after the offscreen pass so the texture it
+    // samples is already populated this frame.
+    render_graph
+        .add_node_edge(base::node::MAIN_PASS, QUAD_PASS)
+        .expect("order quad pass after the main pass");
+    render_graph
+        .add_node_edge(OFFSCREEN_PASS, QUAD_PASS)
+        .expect("order quad pass after the offscreen pass");
+    active_cameras.add(QUAD_CAMERA);
+
+    // Without this, `PixelateMaterial`'s `set = 2` bindings (pixels, levels,
+    // enabled, texture) are never uploaded — the fragment shader would read
+    // whatever garbage is left in those slots rather than our uniforms.
+    render_graph.add_system_node(
+        PIXELATE_MATERIAL_NODE,
+        AssetRenderResourcesNode::<PixelateMaterial>::new(true),
+    );
+    render_graph
+        .add_node_edge(PIXELATE_MATERIAL_NODE, QUAD_PASS)
+        .expect("wire pixelate material bindings into quad pass");
+
+    commands
+        .spawn()
+        .insert_bundle(OrthographicCameraBundle::new_2d())
+        .insert(OffscreenCamera)
+        .insert(Camera {
+            name: Some(OFFSCREEN_CAMERA.to_string()),
+            ..Default::default()
+        });
+
+    commands
+        .spawn()
+        .insert_bundle(OrthographicCameraBundle::new_2d())
+        .insert(Camera {
+            name: Some(QUAD_CAMERA.to_string()),
+            ..Default::default()
+        });
+
+    let pipeline_handle = pipelines.add(PipelineDescriptor::default_config(ShaderStages {
+        vertex: shaders.add(Shader::from_glsl(ShaderStage::Vertex, PIXELATE_VERTEX_SHADER)),
+        fragment: Some(shaders.add(Shader::from_glsl(ShaderStage::Fragment, PIXELATE_FRAGMENT_SHADER))),
+    }));
+
+    let default_settings = PixelateSettings::default();
+    let material = materials.add(PixelateMaterial {
+        pixels: default_settings.pixels,
+        levels: default_settings.levels,
+        enabled: 0.0,
+        texture: render_target,
+    });
+
+    commands.spawn().insert_bundle((
+        meshes.add(Mesh::from(shape::Quad::new(Vec2::new(
+            window.width(),
+            window.height(),
+        )))),
+        material,
+        RenderPipelines::from_pipelines(vec![RenderPipeline::new(pipeline_handle)]),
+        QuadPass,
+        Draw::default(),
+        Transform::default(),
+        GlobalTransform::default(),
+        Visible::default(),
+    ));
+}
+
+/// `P` toggles the pixelation/quantization pass on and off at runtime.
+pub fn toggle_pixelate(
+    keyboard: Res<Input<KeyCode>>,
+    mut settings: ResMut<PixelateSettings>,
+    mut materials: ResMut<Assets<PixelateMaterial>>,
+) {
+    if !keyboard.just_pressed(KeyCode::P) {
+        return;
+    }
+
+    settings.enabled = !settings.enabled;
+    for (_, material) in materials.iter_mut() {
+        material.enabled = if settings.enabled { 1.0 } else { 0.0 };
+        material.pixels = settings.pixels;
+        material.levels = settings.levels;
+    }
+}